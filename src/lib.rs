@@ -1,33 +1,43 @@
-//!
+#![feature(allocator_api)]
+#![feature(impl_trait_in_assoc_type)]
+//! A `Vec`-like collection that pre-initializes its cells to a shared
+//! default, so growing it doesn't pay for per-cell initialization up front.
 
+use std::alloc::{Allocator, Global};
 use std::borrow::Cow;
 use std::mem::MaybeUninit;
 
+pub use std::collections::TryReserveError;
+
 /// A lazily initialized version of `Vec<T>`.
 /// Specifically, `LazyVec<T>` is initialized with a certain length, where each
 /// cell is set to a pointer-sized value (which is the same size as a `usize`)
 ///
+/// The backing storage is parameterized over an [`Allocator`] `A`, defaulting
+/// to [`Global`]. This lets the pre-initialized cells (by default 4096 of
+/// them) live in an arena, bump, or NUMA-pinned allocator instead of the
+/// global one, which is exactly where a custom allocator pays off.
+///
 /// Be careful: Each instance of `LazyVec<T>` creates a `std::sync::LazyLock<T>`
 /// which is used to do cheap pre-initialization. Thus, creating spurious
 /// `LazyVec<T>` values will effectively leak memory.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct LazyVec<T, I = usize>
+pub struct LazyVec<T, I = usize, A = Global>
 where
     T: ToOwned + 'static,
+    A: Allocator,
 {
     label: String,
     len: usize,
-    raw: Vec<Cow<'static, T>>,
+    raw: Vec<Cow<'static, T>, A>,
     default: &'static T,
     __phantom: std::marker::PhantomData<I>,
 }
 
-impl<T, I> LazyVec<T, I>
+impl<T, I> LazyVec<T, I, Global>
 where
     T: ToOwned + 'static,
 {
-    const DEFAULT_LEN: usize = 4 * 1024;
-
     #[inline]
     pub fn new(label: impl Into<String>, default: &'static T) -> Self
     where
@@ -45,15 +55,130 @@ where
     where
         T: ToOwned
     {
+        Self::with_len_in(label, len, default, Global)
+    }
+
+    /// Fallible counterpart of [`LazyVec::new`]. Returns `Err` instead of
+    /// aborting the process if the backing allocation cannot be made.
+    #[inline]
+    pub fn try_new(
+        label: impl Into<String>,
+        default: &'static T,
+    ) -> Result<Self, TryReserveError>
+    where
+        T: ToOwned
+    {
+        Self::try_with_len(label, Self::DEFAULT_LEN, default)
+    }
+
+    /// Fallible counterpart of [`LazyVec::with_len`]. On error, no allocation
+    /// is made and no value is constructed.
+    pub fn try_with_len(
+        label: impl Into<String>,
+        len: usize,
+        default: &'static T,
+    ) -> Result<Self, TryReserveError>
+    where
+        T: ToOwned
+    {
+        Self::try_with_len_in(label, len, default, Global)
+    }
+
+    /// Build a `LazyVec` from an iterator of owned values. `FromIterator`
+    /// alone can't supply the required `label`/`default`, so this builder
+    /// takes them explicitly; it lets callers collect e.g. query results
+    /// straight into a `LazyVec` and iterate them back out without manual
+    /// index loops.
+    pub fn from_iter_labeled(
+        label: impl Into<String>,
+        default: &'static T,
+        iter: impl IntoIterator<Item = <T as ToOwned>::Owned>,
+    ) -> Self
+    where
+        I: From<usize> + Into<usize>,
+    {
+        let mut vec = Self::with_len(label, 0, default);
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, I, A> LazyVec<T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    const DEFAULT_LEN: usize = 4 * 1024;
+
+    /// Like [`LazyVec::new`], but places the backing storage in `alloc`
+    /// instead of [`Global`].
+    #[inline]
+    pub fn new_in(label: impl Into<String>, default: &'static T, alloc: A) -> Self
+    where
+        T: ToOwned
+    {
+        Self::with_len_in(label, Self::DEFAULT_LEN, default, alloc)
+    }
+
+    /// Like [`LazyVec::with_len`], but places the backing storage in `alloc`
+    /// instead of [`Global`].
+    pub fn with_len_in(
+        label: impl Into<String>,
+        len: usize,
+        default: &'static T,
+        alloc: A,
+    ) -> Self
+    where
+        T: ToOwned
+    {
+        let mut raw = Vec::with_capacity_in(len, alloc);
+        raw.resize(len, Cow::Borrowed(default));
         Self {
             label: label.into(),
             len,
-            raw: vec![Cow::Borrowed(default); len],
+            raw,
             default,
             __phantom: std::marker::PhantomData,
         }
     }
 
+    /// Like [`LazyVec::try_new`], but places the backing storage in `alloc`
+    /// instead of [`Global`].
+    #[inline]
+    pub fn try_new_in(
+        label: impl Into<String>,
+        default: &'static T,
+        alloc: A,
+    ) -> Result<Self, TryReserveError>
+    where
+        T: ToOwned
+    {
+        Self::try_with_len_in(label, Self::DEFAULT_LEN, default, alloc)
+    }
+
+    /// Like [`LazyVec::try_with_len`], but places the backing storage in
+    /// `alloc` instead of [`Global`].
+    pub fn try_with_len_in(
+        label: impl Into<String>,
+        len: usize,
+        default: &'static T,
+        alloc: A,
+    ) -> Result<Self, TryReserveError>
+    where
+        T: ToOwned
+    {
+        let mut raw = Vec::new_in(alloc);
+        raw.try_reserve(len)?;
+        raw.resize(len, Cow::Borrowed(default));
+        Ok(Self {
+            label: label.into(),
+            len,
+            raw,
+            default,
+            __phantom: std::marker::PhantomData,
+        })
+    }
+
     pub fn reinit(&mut self, len: usize)
     where
         T: ToOwned
@@ -67,17 +192,62 @@ where
         log::info!("Reinitialized {} in {dur}", self.label);
     }
 
+    /// Fallible counterpart of [`LazyVec::reinit`]. On error, `self` is left
+    /// unchanged: `len` is untouched and `raw` is not partially grown.
+    pub fn try_reinit(&mut self, len: usize) -> Result<(), TryReserveError>
+    where
+        T: ToOwned
+    {
+        self.try_grow_to(len)?;
+        let ((), dur) = tempus_fugit::measure! {{
+            for i in 0..len {
+                self.raw[i] = Cow::Borrowed(self.default);
+            }
+        }};
+        log::info!("Reinitialized {} in {dur}", self.label);
+        Ok(())
+    }
+
     fn grow_to(&mut self, new_len: usize)
     where
         T: ToOwned
     {
         let ((), dur) = tempus_fugit::measure! {{
+            // `resize` also truncates, which would discard the pre-warmed
+            // spare cells left behind by `truncate`/`drain`/`remove`/`clear`
+            // — only ever grow `raw`, and bump `len` separately.
+            if new_len > self.raw.len() {
+                self.raw.resize(new_len, Cow::Borrowed(self.default));
+            }
             if new_len > self.len {
+                self.len = new_len;
+            }
+        }};
+        log::info!("Grew {} in {dur}", self.label);
+    }
+
+    /// Fallible counterpart of [`LazyVec::grow_to`]. On error, `self` is left
+    /// unchanged.
+    fn try_grow_to(&mut self, new_len: usize) -> Result<(), TryReserveError>
+    where
+        T: ToOwned
+    {
+        if new_len > self.raw.len() {
+            let additional = new_len - self.raw.len();
+            self.raw.try_reserve(additional)?;
+        }
+        let ((), dur) = tempus_fugit::measure! {{
+            // See the comment in `grow_to`: only ever grow `raw`, never
+            // truncate it back down to `new_len`.
+            if new_len > self.raw.len() {
                 self.raw.resize(new_len, Cow::Borrowed(self.default));
+            }
+            if new_len > self.len {
                 self.len = new_len;
             }
         }};
         log::info!("Grew {} in {dur}", self.label);
+        Ok(())
     }
 
     pub fn push(&mut self, val: <T as ToOwned>::Owned) -> I
@@ -91,10 +261,33 @@ where
             self.len += 1;
         } else {
             self.raw.push(val);
+            self.len += 1;
         }
         idx
     }
 
+    /// Fallible counterpart of [`LazyVec::push`]. On error, `self` is left
+    /// unchanged (len untouched, no partially-grown `raw`).
+    pub fn try_push(
+        &mut self,
+        val: <T as ToOwned>::Owned,
+    ) -> Result<I, TryReserveError>
+    where
+        I: From<usize> + Into<usize>,
+    {
+        let val = Cow::Owned(val);
+        let idx = I::from(self.len);
+        if self.len < self.raw.len() { // extra cells available
+            self.raw[self.len] = val;
+            self.len += 1;
+        } else {
+            self.raw.try_reserve(1)?;
+            self.raw.push(val);
+            self.len += 1;
+        }
+        Ok(idx)
+    }
+
     #[track_caller]
     pub fn pop(&mut self) -> <T as ToOwned>::Owned
     where
@@ -185,12 +378,154 @@ where
             std::mem::transmute_copy::<_, [&mut T; N]>(&out)
         }
     }
+
+    /// Remove the cells in `range`, yielding their owned values in order.
+    /// Unlike `Vec::drain`, the vacated cells are reset to
+    /// `Cow::Borrowed(self.default)` and the tail is compacted down over
+    /// them instead of shrinking `raw`, so the pre-initialized capacity is
+    /// preserved for future `push`es.
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, I, A>
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let len = self.len;
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(&n) => n,
+            std::ops::Bound::Excluded(&n) => n + 1,
+            std::ops::Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            std::ops::Bound::Included(&n) => n + 1,
+            std::ops::Bound::Excluded(&n) => n,
+            std::ops::Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end && end <= len,
+            "Drain range out of bounds (failed: {start} <= {end} <= {len})"
+        );
+        Drain { vec: self, start, cur: start, end }
+    }
+
+    /// Remove and yield every cell for which `pred` returns `true`, in
+    /// order. As with [`LazyVec::drain`], removed cells are reset to
+    /// `Cow::Borrowed(self.default)` and the surviving cells are compacted
+    /// down, preserving the pre-initialized capacity.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, I, A, F>
+    where
+        T: ToOwned<Owned = T>,
+        F: FnMut(&mut T) -> bool,
+    {
+        let old_len = self.len;
+        ExtractIf { vec: self, read: 0, write: 0, old_len, pred }
+    }
+
+    /// Demote every `Cow::Owned` cell at or above `self.len` back to
+    /// `Cow::Borrowed(self.default)`, dropping the owned value and releasing
+    /// whatever heap memory it held. Returns the number of cells freed.
+    pub fn reclaim(&mut self) -> usize {
+        let len = self.len;
+        self.reclaim_above(len)
+    }
+
+    /// Like [`LazyVec::reclaim`], but only demotes cells at or above
+    /// `threshold` instead of `self.len`. `threshold` is clamped to
+    /// `self.len`, so a cell holding a logically-live value (index `<
+    /// self.len`) is never demoted regardless of what's passed in.
+    pub fn reclaim_above(&mut self, threshold: usize) -> usize {
+        let threshold = threshold.max(self.len);
+        let mut freed = 0usize;
+        let ((), dur) = tempus_fugit::measure! {{
+            for cell in self.raw.iter_mut().skip(threshold) {
+                if matches!(cell, Cow::Owned(_)) {
+                    *cell = Cow::Borrowed(self.default);
+                    freed += 1;
+                }
+            }
+        }};
+        log::info!("Reclaimed {freed} cell(s) in {} in {dur}", self.label);
+        freed
+    }
+
+    /// Shrink `raw` toward `min_len` via the allocator, while always keeping
+    /// at least `self.len` cells. Trades the pre-initialization advantage
+    /// for lower resident memory after a usage spike.
+    pub fn shrink_to(&mut self, min_len: usize) {
+        let target = min_len.max(self.len);
+        let ((), dur) = tempus_fugit::measure! {{
+            self.raw.truncate(target);
+            self.raw.shrink_to(target);
+        }};
+        log::info!("Shrunk {} to {target} cell(s) in {dur}", self.label);
+    }
+
+    /// Lower `self.len` to `len`, resetting the vacated cells to
+    /// `Cow::Borrowed(self.default)` instead of deallocating them, so the
+    /// capacity stays pre-warmed. A no-op if `len >= self.len`.
+    pub fn truncate(&mut self, len: usize)
+    where
+        T: ToOwned
+    {
+        if len < self.len {
+            for i in len..self.len {
+                self.raw[i] = Cow::Borrowed(self.default);
+            }
+            self.len = len;
+        }
+    }
+
+    /// Remove every cell, resetting them all to `Cow::Borrowed(self.default)`
+    /// instead of deallocating.
+    #[inline]
+    pub fn clear(&mut self)
+    where
+        T: ToOwned
+    {
+        self.truncate(0);
+    }
+
+    /// Shift `self.raw[idx..self.len]` up by one, growing the buffer through
+    /// [`LazyVec::grow_to`] when there's no spare cell, then write `val` at
+    /// `idx`.
+    #[track_caller]
+    pub fn insert(&mut self, idx: usize, val: <T as ToOwned>::Owned)
+    where
+        T: ToOwned
+    {
+        let len = self.len;
+        assert!(idx <= len, "Index out of bounds (failed: {idx} <= {len})");
+        if len == self.raw.len() { // no spare cell available
+            self.grow_to(len + 1);
+            self.len = len; // undo grow_to's bump; accounted for below instead
+        }
+        self.raw[idx..=len].rotate_right(1);
+        self.raw[idx] = Cow::Owned(val);
+        self.len = len + 1;
+    }
+
+    /// Shift `self.raw[idx + 1..self.len]` down by one, returning the owned
+    /// value that was at `idx`, and reset the now-free tail cell to
+    /// `Cow::Borrowed(self.default)`.
+    #[track_caller]
+    pub fn remove(&mut self, idx: usize) -> <T as ToOwned>::Owned
+    where
+        T: ToOwned
+    {
+        let len = self.len;
+        assert!(idx < len, "Index out of bounds (failed: {idx} < {len})");
+        let mut retval = Cow::Borrowed(self.default);
+        std::mem::swap(&mut self.raw[idx], &mut retval);
+        self.raw[idx..len].rotate_left(1);
+        self.len = len - 1;
+        retval.into_owned()
+    }
 }
 
-impl<T, I> std::fmt::Debug for LazyVec<T, I>
+impl<T, I, A> std::fmt::Debug for LazyVec<T, I, A>
 where
     T: ToOwned<Owned = T>,
     T: std::fmt::Debug,
+    A: Allocator,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LazyVec")
@@ -201,10 +536,11 @@ where
     }
 }
 
-impl<T, I> std::ops::Index<I> for LazyVec<T, I>
+impl<T, I, A> std::ops::Index<I> for LazyVec<T, I, A>
 where
     T: ToOwned,
     I: From<usize> + Into<usize>,
+    A: Allocator,
 {
     type Output = T;
 
@@ -216,10 +552,11 @@ where
     }
 }
 
-impl<T, I> std::ops::IndexMut<I> for LazyVec<T, I>
+impl<T, I, A> std::ops::IndexMut<I> for LazyVec<T, I, A>
 where
     T: ToOwned<Owned = T>,
     I: From<usize> + Into<usize>,
+    A: Allocator,
 {
     #[track_caller]
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
@@ -229,6 +566,198 @@ where
     }
 }
 
+/// Iterator returned by [`LazyVec::drain`]. Yields the owned value of each
+/// drained cell in order; on drop (including early drop, e.g. a panicking
+/// consumer) any not-yet-yielded cells in the range are reset to
+/// `Cow::Borrowed(self.default)` and `self.len` is fixed up, so no owned
+/// value is ever stranded above `len`.
+pub struct Drain<'a, T, I, A = Global>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    vec: &'a mut LazyVec<T, I, A>,
+    start: usize,
+    cur: usize,
+    end: usize,
+}
+
+impl<'a, T, I, A> Iterator for Drain<'a, T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    type Item = <T as ToOwned>::Owned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+        let mut retval = Cow::Borrowed(self.vec.default);
+        std::mem::swap(&mut self.vec.raw[self.cur], &mut retval);
+        self.cur += 1;
+        Some(retval.into_owned())
+    }
+}
+
+impl<'a, T, I, A> Drop for Drain<'a, T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        // Reset any cells this iterator never got around to yielding.
+        for i in self.cur..self.end {
+            self.vec.raw[i] = Cow::Borrowed(self.vec.default);
+        }
+        // `raw[start..end]` is now all borrowed defaults; rotate them to the
+        // tail of `raw[start..len]` so the kept cells slide down to `start`.
+        let removed = self.end - self.start;
+        if removed > 0 {
+            let len = self.vec.len;
+            self.vec.raw[self.start..len].rotate_left(removed);
+            self.vec.len -= removed;
+        }
+    }
+}
+
+/// Iterator returned by [`LazyVec::extract_if`]. Yields the owned value of
+/// each cell matching the predicate, in order; on drop (including early
+/// drop, e.g. a panicking predicate) every cell not yet visited is treated
+/// as kept — `pred` is never invoked again during unwinding — and is
+/// compacted down mechanically alongside the cells already known to survive,
+/// with `self.len` fixed up to match.
+pub struct ExtractIf<'a, T, I, A, F>
+where
+    T: ToOwned<Owned = T> + 'static,
+    A: Allocator,
+    F: FnMut(&mut T) -> bool,
+{
+    vec: &'a mut LazyVec<T, I, A>,
+    read: usize,
+    write: usize,
+    old_len: usize,
+    pred: F,
+}
+
+impl<'a, T, I, A, F> Iterator for ExtractIf<'a, T, I, A, F>
+where
+    T: ToOwned<Owned = T> + 'static,
+    A: Allocator,
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.read < self.old_len {
+            let matched = (self.pred)(self.vec.raw[self.read].to_mut());
+            if matched {
+                let mut retval = Cow::Borrowed(self.vec.default);
+                std::mem::swap(&mut self.vec.raw[self.read], &mut retval);
+                self.read += 1;
+                return Some(retval.into_owned());
+            } else {
+                if self.write != self.read {
+                    self.vec.raw.swap(self.write, self.read);
+                }
+                self.write += 1;
+                self.read += 1;
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, I, A, F> Drop for ExtractIf<'a, T, I, A, F>
+where
+    T: ToOwned<Owned = T> + 'static,
+    A: Allocator,
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        // Don't re-invoke `pred` here: if it panicked mid-iteration, calling
+        // it again on the same cell during unwinding would panic in a
+        // destructor and abort the process. Instead, treat every cell from
+        // `read` onward as kept and compact it down mechanically: `raw[write
+        // ..read)` holds the holes left by already-extracted cells, and
+        // `raw[read..old_len)` holds the untouched survivors, so rotating
+        // the holes to the tail of `raw[write..old_len)` slides the
+        // survivors down to `write`.
+        let remaining = self.old_len - self.read;
+        if self.read != self.write {
+            self.vec.raw[self.write..self.old_len].rotate_left(self.read - self.write);
+        }
+        self.vec.len = self.write + remaining;
+    }
+}
+
+/// Owning iterator returned by `LazyVec::into_iter`. Yields the owned value
+/// of each cell in `0..len`, taking ownership of `Cow::Owned` cells and
+/// cloning the borrowed default where a cell was never written.
+pub struct IntoIter<T, I, A = Global>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    iter: std::iter::Take<std::vec::IntoIter<Cow<'static, T>, A>>,
+    __phantom: std::marker::PhantomData<I>,
+}
+
+impl<T, I, A> Iterator for IntoIter<T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    type Item = <T as ToOwned>::Owned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Cow::into_owned)
+    }
+}
+
+impl<T, I, A> IntoIterator for LazyVec<T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    type Item = <T as ToOwned>::Owned;
+    type IntoIter = IntoIter<T, I, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let len = self.len;
+        IntoIter { iter: self.raw.into_iter().take(len), __phantom: std::marker::PhantomData }
+    }
+}
+
+impl<'a, T, I, A> IntoIterator for &'a LazyVec<T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+{
+    type Item = &'a T;
+    type IntoIter = impl DoubleEndedIterator<Item = &'a T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // `iter()` walks `0..len` in *reverse*, but the owning `IntoIterator`
+        // above yields forward `0..len` order — reverse it back here so `for
+        // x in &v` and `for x in v` agree.
+        self.iter().rev()
+    }
+}
+
+impl<T, I, A> Extend<<T as ToOwned>::Owned> for LazyVec<T, I, A>
+where
+    T: ToOwned + 'static,
+    A: Allocator,
+    I: From<usize> + Into<usize>,
+{
+    fn extend<It: IntoIterator<Item = <T as ToOwned>::Owned>>(&mut self, iter: It) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
 
 #[macro_export]
 /// Create a new `LazyVec<T>` value.
@@ -240,6 +769,23 @@ macro_rules! lazy_vec {
         static DEFAULT: LazyLock<$value_type> = LazyLock::new(|| $default.into());
         LazyVec::new($label, &*DEFAULT)
     }};
+    // The `; try` arms must come before the `$len:expr` arms: once the
+    // `expr` fragment starts parsing at the reserved `try` keyword it hard
+    // errors instead of letting macro_rules fall through to try another arm.
+    ($default:expr ; as $value_type:ty ; named $label:expr ; try) => {{
+        use $crate::LazyVec;
+        use std::sync::LazyLock;
+
+        static DEFAULT: LazyLock<$value_type> = LazyLock::new(|| $default.into());
+        LazyVec::try_new($label, &*DEFAULT)
+    }};
+    ($default:expr ; as $value_type:ty ; named $label:expr; $len:expr ; try) => {{
+        use $crate::LazyVec;
+        use std::sync::LazyLock;
+
+        static DEFAULT: LazyLock<$value_type> = LazyLock::new(|| $default.into());
+        LazyVec::try_with_len($label, $len, &*DEFAULT)
+    }};
     ($default:expr ; as $value_type:ty ; named $label:expr; $len:expr) => {{
         use $crate::LazyVec;
         use std::sync::LazyLock;
@@ -253,16 +799,237 @@ macro_rules! lazy_vec {
 #[cfg(test)]
 mod tests {
     use super::LazyVec;
+    use std::borrow::Cow;
+
+    static DEFAULT_I32: i32 = -1;
 
     #[test]
     fn init_with_default_len() {
         let v: LazyVec<_, usize> = lazy_vec!["a value"; as String; named "Example"];
-        // TODO
+        assert_eq!(v.len(), 4096);
     }
 
     #[test]
     fn init_with_custom_len() {
         let v: LazyVec<_, usize> = lazy_vec!["a value"; as String; named "Example"; 1024];
-        // TODO
+        assert_eq!(v.len(), 1024);
+    }
+
+    #[test]
+    fn try_init_with_default_len() {
+        let v: Result<LazyVec<_, usize>, _> =
+            lazy_vec!["a value"; as String; named "Example"; try];
+        assert_eq!(v.unwrap().len(), 4096);
+    }
+
+    #[test]
+    fn try_init_with_custom_len() {
+        let v: Result<LazyVec<_, usize>, _> =
+            lazy_vec!["a value"; as String; named "Example"; 1024; try];
+        assert_eq!(v.unwrap().len(), 1024);
+    }
+
+    #[test]
+    fn try_reinit_after_truncate_does_not_panic() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 10, &DEFAULT_I32);
+        v.truncate(4);
+        assert!(v.try_reinit(6).is_ok());
+        assert_eq!(v.len(), 6);
+        // The pre-warmed capacity from the original `with_len(10)` must
+        // survive the truncate/reinit round trip, not get discarded by a
+        // `resize` that shrinks `raw` down to the smaller `new_len`.
+        assert_eq!(v.raw.len(), 10);
+    }
+
+    #[test]
+    fn init_with_custom_len_in_global() {
+        let v: LazyVec<_, usize, std::alloc::Global> =
+            LazyVec::with_len_in("Example", 1024, Box::leak(Box::new("a value".to_string())), std::alloc::Global);
+        assert_eq!(v.len(), 1024);
+    }
+
+    #[test]
+    fn new_in_with_global_allocator_matches_new() {
+        let v: LazyVec<i32, usize, std::alloc::Global> =
+            LazyVec::new_in("Example", &DEFAULT_I32, std::alloc::Global);
+        assert_eq!(v.len(), 4096);
+    }
+
+    #[test]
+    fn drain_compacts_and_preserves_capacity() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        let drained: Vec<i32> = v.drain(1..3).collect();
+        assert_eq!(drained, vec![1, 2]);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 3);
+        assert_eq!(v[2], 4);
+        assert_eq!(v.raw.len(), 5);
+    }
+
+    #[test]
+    fn extract_if_removes_matching_and_compacts() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        let extracted: Vec<i32> = v.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(extracted, vec![0, 2, 4]);
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0], 1);
+        assert_eq!(v[1], 3);
+    }
+
+    #[test]
+    fn extract_if_early_drop_keeps_unvisited_cells() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        {
+            let mut iter = v.extract_if(|x| *x % 2 == 0);
+            assert_eq!(iter.next(), Some(0));
+            // Dropped here without visiting indices 1..5; `drop` must not
+            // re-invoke `pred` on them, so they're all kept as-is.
+        }
+        assert_eq!(v.len(), 4);
+        assert_eq!(v[0], 1);
+        assert_eq!(v[1], 2);
+        assert_eq!(v[2], 3);
+        assert_eq!(v[3], 4);
+    }
+
+    #[test]
+    fn reclaim_above_demotes_owned_cells_past_threshold() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        // Append owned cells directly past `len`, without going through
+        // `grow_to` (which would bump `len` itself).
+        v.raw.push(Cow::Owned(10));
+        v.raw.push(Cow::Owned(11));
+        v.raw.push(Cow::Owned(12));
+        let freed = v.reclaim_above(5);
+        assert_eq!(freed, 3);
+        // Live cells (index < len) are untouched; only the spare cells past
+        // `len` were demoted.
+        assert_eq!(v.len(), 5);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 1);
+        assert_eq!(v[2], 2);
+        assert_eq!(v[3], 3);
+        assert_eq!(v[4], 4);
+    }
+
+    #[test]
+    fn reclaim_above_clamps_threshold_below_len() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        // A threshold below `len` must not demote any logically-live cell.
+        let freed = v.reclaim_above(2);
+        assert_eq!(freed, 0);
+        assert_eq!(v.len(), 5);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 1);
+        assert_eq!(v[2], 2);
+        assert_eq!(v[3], 3);
+        assert_eq!(v[4], 4);
+    }
+
+    #[test]
+    fn reclaim_is_noop_when_nothing_is_stale() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..3 { v.push(i); }
+        assert_eq!(v.reclaim(), 0);
+    }
+
+    #[test]
+    fn shrink_to_keeps_at_least_len_cells() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 10, &DEFAULT_I32);
+        v.truncate(4);
+        v.shrink_to(0);
+        assert_eq!(v.len(), 4);
+        assert_eq!(v.raw.len(), 4);
+    }
+
+    #[test]
+    fn into_iter_yields_owned_values_in_index_order() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..3 { v.push(i); }
+        let collected: Vec<i32> = v.into_iter().collect();
+        assert_eq!(collected, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn ref_into_iter_matches_owning_into_iter_order() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..3 { v.push(i); }
+        // `&v` and `v` must agree on order: both forward `0..len`, even
+        // though `iter()` itself walks in reverse.
+        let collected: Vec<&i32> = (&v).into_iter().collect();
+        assert_eq!(collected, vec![&0, &1, &2]);
+    }
+
+    #[test]
+    fn from_iter_labeled_collects_in_order() {
+        let v: LazyVec<i32, usize> =
+            LazyVec::from_iter_labeled("Example", &DEFAULT_I32, [10, 20, 30]);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0], 10);
+        assert_eq!(v[1], 20);
+        assert_eq!(v[2], 30);
+    }
+
+    #[test]
+    fn extend_appends_after_existing_values() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        v.push(1);
+        v.extend([2, 3]);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0], 1);
+        assert_eq!(v[1], 2);
+        assert_eq!(v[2], 3);
+    }
+
+    #[test]
+    fn truncate_resets_vacated_cells_to_default() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..5 { v.push(i); }
+        v.truncate(2);
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 1);
+        assert!(matches!(v.raw[2], Cow::Borrowed(_)));
+        assert!(matches!(v.raw[4], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn clear_resets_all_cells_to_default() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..3 { v.push(i); }
+        v.clear();
+        assert_eq!(v.len(), 0);
+        assert!(matches!(v.raw[0], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn insert_shifts_tail_up() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..3 { v.push(i); } // [0, 1, 2]
+        v.insert(1, 99);
+        assert_eq!(v.len(), 4);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 99);
+        assert_eq!(v[2], 1);
+        assert_eq!(v[3], 2);
+    }
+
+    #[test]
+    fn remove_shifts_tail_down_and_resets_vacated_cell() {
+        let mut v: LazyVec<i32, usize> = LazyVec::with_len("Example", 0, &DEFAULT_I32);
+        for i in 0..4 { v.push(i); } // [0, 1, 2, 3]
+        let removed = v.remove(1);
+        assert_eq!(removed, 1);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0], 0);
+        assert_eq!(v[1], 2);
+        assert_eq!(v[2], 3);
+        assert!(matches!(v.raw[3], Cow::Borrowed(_)));
     }
 }